@@ -0,0 +1,488 @@
+//! Path, project, and language helpers used to build event bodies. They have no Zed
+//! host dependencies, so other extensions and test harnesses can call them directly.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const DEFAULT_MAX_PATH_LEN: usize = 2048;
+const MIN_MAX_PATH_LEN: usize = 64;
+const MAX_MAX_PATH_LEN: usize = 8192;
+const MAX_PROJECT_NAME_CHARS: usize = 128;
+
+/// Decodes `%XX` escapes; malformed sequences are kept as written and invalid UTF-8 is
+/// replaced.
+pub(crate) fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Removes the root of an absolute path (already using `/` separators): a UNC
+/// `//server/share/` prefix, a drive letter such as `C:/`, or leading slashes.
+pub(crate) fn strip_absolute_prefix(path: &str) -> &str {
+    if let Some(unc) = path.strip_prefix("//") {
+        // Skip the server and share names.
+        return unc.splitn(3, '/').nth(2).unwrap_or("");
+    }
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes.len() == 2 || bytes[2] == b'/');
+    let path = if has_drive { &path[2..] } else { path };
+    path.trim_start_matches('/')
+}
+
+/// Parses `CODETIME_MAX_PATH_LEN` (64..=8192); other values use the default of 2048.
+pub(crate) fn parse_max_path_len(raw: Option<&str>) -> usize {
+    raw.and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|n| (MIN_MAX_PATH_LEN..=MAX_MAX_PATH_LEN).contains(n))
+        .unwrap_or(DEFAULT_MAX_PATH_LEN)
+}
+
+thread_local! {
+    // Parsed on first use, like CODETIME_LANGUAGE_MAP.
+    static MAX_PATH_LEN: usize =
+        parse_max_path_len(env::var("CODETIME_MAX_PATH_LEN").ok().as_deref());
+}
+
+/// Longest `relativeFile` sent, in bytes: `CODETIME_MAX_PATH_LEN` or 2048.
+pub fn max_relative_path_len() -> usize {
+    MAX_PATH_LEN.with(|len| *len)
+}
+
+/// Unicode NFC form of `path`, so an `é` typed as one code point (Linux) or as `e` plus a
+/// combining accent (macOS) compares equal. ASCII is returned as is; without the `nfc`
+/// feature every path is.
+pub(crate) fn normalize_nfc(path: &str) -> Cow<'_, str> {
+    if path.is_ascii() {
+        return Cow::Borrowed(path);
+    }
+    #[cfg(feature = "nfc")]
+    {
+        use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+        if is_nfc_quick(path.chars()) != IsNormalized::Yes {
+            return Cow::Owned(path.nfc().collect());
+        }
+    }
+    Cow::Borrowed(path)
+}
+
+/// Sanitizes relative file path: percent-decoded and NFC-normalized first, absolute prefixes
+/// stripped (after trimming, so `/home/u/a.rs` becomes `home/u/a.rs`), then no traversal or
+/// `.` segments, reasonable length, forward slashes.
+pub fn sanitize_relative_path(input: &str) -> String {
+    let decoded = percent_decode(input);
+    let normalized = normalize_nfc(&decoded);
+    let s = normalized.trim();
+    if s.is_empty() {
+        return "unknown".to_string();
+    }
+    let no_back = s.replace('\\', "/");
+    let parts: Vec<&str> = strip_absolute_prefix(&no_back)
+        .split('/')
+        .filter(|p| !p.is_empty() && *p != "." && *p != "..")
+        .collect();
+    let joined = parts.join("/");
+    if joined.is_empty() {
+        return "unknown".to_string();
+    }
+    fit_relative_path(joined, max_relative_path_len())
+}
+
+/// Shortens a `/`-joined path to at most `max_len` bytes by dropping whole leading
+/// segments, so the file name survives. A file name that is too long on its own is cut at
+/// the last char boundary that fits.
+pub(crate) fn fit_relative_path(path: String, max_len: usize) -> String {
+    if path.len() <= max_len {
+        return path;
+    }
+    let mut rest = path.as_str();
+    while rest.len() > max_len {
+        match rest.split_once('/') {
+            Some((_, tail)) => rest = tail,
+            None => {
+                let mut end = max_len;
+                while !rest.is_char_boundary(end) {
+                    end -= 1;
+                }
+                return rest[..end].to_string();
+            }
+        }
+    }
+    rest.to_string()
+}
+
+/// Like `sanitize_relative_path`, but re-adds a leading `./` when the input had one
+/// (`./` or `.\\`).
+pub(crate) fn sanitize_relative_path_keep_dot(input: &str) -> String {
+    let sanitized = sanitize_relative_path(input);
+    let trimmed = input.trim_start();
+    if (trimmed.starts_with("./") || trimmed.starts_with(".\\")) && sanitized != "unknown" {
+        format!("./{}", sanitized)
+    } else {
+        sanitized
+    }
+}
+
+/// Returns the last path component, treating both `/` and `\\` as separators so
+/// Windows paths resolve the same way on every host.
+pub(crate) fn last_path_component(path: &str) -> Option<&str> {
+    path.trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|n| !n.is_empty())
+}
+
+/// Folder names that say nothing about the project inside them (compared ignoring case).
+const GENERIC_ROOT_NAMES: &[&str] = &[
+    "workspace",
+    "workspaces",
+    "project",
+    "projects",
+    "repo",
+    "repos",
+    "code",
+    "src",
+    "app",
+    "monorepo",
+];
+
+/// Folder name of a worktree root (either separator), or `unknown`. Surrounding
+/// whitespace is trimmed, inner runs of whitespace become one space, control characters
+/// are dropped, and the name is cut to 128 characters. When the folder has a generic name
+/// such as `workspace` or `projects`, the package name from `{root}/Cargo.toml` or else
+/// `{root}/package.json` is used instead, if either can be read.
+pub fn project_name_from_root(root_path: &str) -> String {
+    let folder = clean_project_name(last_path_component(root_path).unwrap_or(""));
+    let generic = GENERIC_ROOT_NAMES
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(&folder));
+    if generic {
+        if let Some(name) = manifest_project_name(Path::new(root_path)) {
+            return name;
+        }
+    }
+    folder
+}
+
+/// Package name from `Cargo.toml`, else `package.json`, in `root`. Missing, unreadable,
+/// or unparsable files are skipped; only the local file system is read.
+fn manifest_project_name(root: &Path) -> Option<String> {
+    let from_cargo = fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|text| cargo_package_name(&text));
+    from_cargo
+        .or_else(|| {
+            fs::read_to_string(root.join("package.json"))
+                .ok()
+                .and_then(|text| package_json_name(&text))
+        })
+        .map(|name| clean_project_name(&name))
+        .filter(|name| name != "unknown")
+}
+
+/// `name = "..."` from the `[package]` table of a `Cargo.toml`. A virtual workspace
+/// manifest (only `[workspace]`) has no name, so it gives `None`.
+pub(crate) fn cargo_package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+            continue;
+        }
+        if !in_package {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() != "name" {
+            continue;
+        }
+        let value = value.trim();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let (name, _) = value[1..].split_once(quote)?;
+        return Some(name.to_string());
+    }
+    None
+}
+
+/// The top-level string `name` of a `package.json`.
+pub(crate) fn package_json_name(manifest: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(manifest).ok()?;
+    value.get("name")?.as_str().map(str::to_string)
+}
+
+/// Collapses whitespace, drops control characters, and caps the length; blank becomes
+/// `unknown`.
+fn clean_project_name(raw: &str) -> String {
+    let collapsed = raw
+        .split_whitespace()
+        .map(|word| word.chars().filter(|c| !c.is_control()).collect::<String>())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name: String = collapsed.chars().take(MAX_PROJECT_NAME_CHARS).collect();
+    match name.trim_end() {
+        "" => "unknown".to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Branch checked out in the repository at `root`, read from `{root}/.git/HEAD`. `None`
+/// for a detached HEAD, a missing or unreadable file, or a `.git` that is not a directory
+/// (linked worktrees and submodules).
+pub fn read_git_branch(root: &str) -> Option<String> {
+    let head = fs::read_to_string(Path::new(root).join(".git").join("HEAD")).ok()?;
+    head.trim()
+        .strip_prefix("ref:")?
+        .trim()
+        .strip_prefix("refs/heads/")
+        .filter(|branch| !branch.is_empty())
+        .map(str::to_string)
+}
+
+/// Well-known file names (lowercase) whose language is not given by their extension.
+pub(crate) const NAMED_FILES: &[(&str, &str)] = &[
+    ("dockerfile", "dockerfile"),
+    ("containerfile", "dockerfile"),
+    ("makefile", "makefile"),
+    ("gnumakefile", "makefile"),
+    ("gemfile", "ruby"),
+    ("rakefile", "ruby"),
+    ("vagrantfile", "ruby"),
+    ("jenkinsfile", "groovy"),
+    ("cmakelists.txt", "cmake"),
+    ("build.bazel", "starlark"),
+    ("workspace.bazel", "starlark"),
+    ("meson.build", "meson"),
+    ("build.gradle", "gradle"),
+    ("build.gradle.kts", "gradle"),
+    ("settings.gradle", "gradle"),
+    ("pom.xml", "maven"),
+    ("cargo.toml", "toml"),
+    ("cargo.lock", "toml"),
+    ("justfile", "just"),
+    (".gitignore", "gitignore"),
+    (".env", "dotenv"),
+];
+
+/// Looks up `file_name` in `NAMED_FILES`, ignoring case.
+fn language_from_file_name(file_name: &str) -> Option<&'static str> {
+    NAMED_FILES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(file_name))
+        .map(|&(_, language)| language)
+}
+
+/// Maps known double extensions (`types.d.ts`, `archive.tar.gz`). Only the last two
+/// dot-separated segments are considered, and a non-empty stem must precede them.
+fn language_from_compound_extension(file_name: &str) -> Option<&'static str> {
+    let mut parts = file_name.rsplitn(3, '.');
+    let last = parts.next()?;
+    let second = parts.next()?;
+    parts.next().filter(|stem| !stem.is_empty())?;
+    let compound = format!("{}.{}", second, last).to_lowercase();
+    let language = match compound.as_str() {
+        "d.ts" | "d.mts" | "d.cts" => "typescript-declaration",
+        "test.ts" | "test.tsx" | "spec.ts" | "spec.tsx" => "typescript",
+        "min.js" | "test.js" | "test.jsx" | "spec.js" | "spec.jsx" => "javascript",
+        "module.css" | "module.scss" => "css",
+        "tar.gz" | "tar.bz2" => "archive",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Parses `CODETIME_LANGUAGE_MAP` (`ext=language,ext=language`). Keys are lowercased
+/// and may carry a leading dot; pairs missing either side are skipped.
+pub(crate) fn parse_language_map(raw: &str) -> HashMap<String, String> {
+    raw.split(',')
+        .filter_map(|pair| {
+            let (ext, language) = pair.split_once('=')?;
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            let language = language.trim();
+            if ext.is_empty() || language.is_empty() {
+                return None;
+            }
+            Some((ext, language.to_string()))
+        })
+        .collect()
+}
+
+/// Built-in extension (lowercase, no dot) to language table.
+pub(crate) const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("py", "python"),
+    ("js", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("sql", "sql"),
+    ("md", "markdown"),
+    ("json", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("css", "css"),
+    ("scss", "css"),
+    ("less", "css"),
+    ("sh", "shell"),
+    ("bash", "shell"),
+    ("zsh", "shell"),
+    ("go", "go"),
+    ("mod", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("swift", "swift"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("hxx", "cpp"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hrl", "erlang"),
+    ("scala", "scala"),
+    ("sc", "scala"),
+    ("fs", "fsharp"),
+    ("fsi", "fsharp"),
+    ("fsx", "fsharp"),
+    ("zig", "zig"),
+    ("v", "v"),
+    ("nim", "nim"),
+    ("cr", "crystal"),
+    ("hbs", "handlebars"),
+    ("handlebars", "handlebars"),
+    ("mustache", "mustache"),
+    ("ejs", "ejs"),
+    ("jinja", "jinja"),
+    ("j2", "jinja"),
+    ("njk", "nunjucks"),
+    ("prisma", "prisma"),
+    ("graphql", "graphql"),
+    ("gql", "graphql"),
+    ("proto", "protobuf"),
+    ("thrift", "thrift"),
+    ("ipynb", "jupyter"),
+    ("rmd", "rmarkdown"),
+    ("qmd", "quarto"),
+    ("tex", "latex"),
+    ("ltx", "latex"),
+    ("cls", "latex"),
+    ("sty", "latex"),
+    ("bib", "bibtex"),
+];
+
+thread_local! {
+    // Parsed on first use; the extension runs on a single thread for its lifetime.
+    pub(crate) static USER_LANGUAGE_MAP: HashMap<String, String> =
+        parse_language_map(&env::var("CODETIME_LANGUAGE_MAP").unwrap_or_default());
+}
+
+/// Language for a file path: well-known file names first, then double extensions,
+/// `CODETIME_LANGUAGE_MAP`, and the built-in extension table. Unknown extensions come back
+/// lowercased; no extension gives `unknown`.
+pub fn language_from_extension(relative_file: &str) -> String {
+    USER_LANGUAGE_MAP.with(|user| language_from_extension_with(relative_file, user))
+}
+
+/// `language_from_extension` with `user` in place of `CODETIME_LANGUAGE_MAP`.
+pub(crate) fn language_from_extension_with(
+    relative_file: &str,
+    user: &HashMap<String, String>,
+) -> String {
+    let file_name = last_path_component(relative_file);
+    if let Some(language) = file_name.and_then(language_from_file_name) {
+        return language.to_string();
+    }
+    if let Some(language) = file_name.and_then(language_from_compound_extension) {
+        return language.to_string();
+    }
+    Path::new(relative_file)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| {
+            let e: String = e.to_lowercase();
+            if let Some(language) = user.get(&e) {
+                return language.clone();
+            }
+            EXTENSION_LANGUAGES
+                .iter()
+                .find(|(ext, _)| *ext == e)
+                .map_or(e.as_str(), |&(_, language)| language)
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Parses `CODETIME_WRITE_EVENTS`: comma-separated event names reported as `write` in
+/// addition to the built-in ones.
+pub(crate) fn parse_write_events(raw: &str) -> HashSet<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+thread_local! {
+    // Parsed on first use, like CODETIME_LANGUAGE_MAP.
+    pub(crate) static WRITE_EVENTS: HashSet<String> =
+        parse_write_events(&env::var("CODETIME_WRITE_EVENTS").unwrap_or_default());
+}
+
+/// `write` for built-in write events and any listed in `extra_writes`, else `read`.
+pub(crate) fn operation_type_with(
+    event_type: &str,
+    extra_writes: &HashSet<String>,
+) -> &'static str {
+    match event_type {
+        "fileSaved" | "fileEdited" | "fileCreated" | "fileAddedLine" => "write",
+        _ if extra_writes.contains(event_type) => "write",
+        _ => "read",
+    }
+}
+
+/// `write` or `read` for an event type, honoring `CODETIME_WRITE_EVENTS`.
+pub fn operation_type_for_event(event_type: &str) -> &'static str {
+    WRITE_EVENTS.with(|extra| operation_type_with(event_type, extra))
+}
@@ -0,0 +1,224 @@
+use codetime::utils;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn language_from_extension_covers_names_compounds_and_extensions() {
+    assert_eq!(utils::language_from_extension("src/lib.rs"), "rust");
+    assert_eq!(utils::language_from_extension("web/App.TSX"), "typescript");
+    assert_eq!(
+        utils::language_from_extension("docker/Dockerfile"),
+        "dockerfile"
+    );
+    assert_eq!(
+        utils::language_from_extension("types/index.d.ts"),
+        "typescript-declaration"
+    );
+    assert_eq!(utils::language_from_extension("notes.weird"), "weird");
+    assert_eq!(utils::language_from_extension("bin/deploy"), "unknown");
+}
+
+#[test]
+fn sanitize_relative_path_strips_roots_and_traversal() {
+    assert_eq!(utils::sanitize_relative_path("src/lib.rs"), "src/lib.rs");
+    assert_eq!(
+        utils::sanitize_relative_path("../../etc/passwd"),
+        "etc/passwd"
+    );
+    assert_eq!(
+        utils::sanitize_relative_path("C:\\work\\app\\main.rs"),
+        "work/app/main.rs"
+    );
+    assert_eq!(utils::sanitize_relative_path("my%20file.rs"), "my file.rs");
+    assert_eq!(utils::sanitize_relative_path("  "), "unknown");
+}
+
+#[test]
+fn project_name_from_root_uses_last_folder() {
+    assert_eq!(
+        utils::project_name_from_root("/home/dev/code-time/"),
+        "code-time"
+    );
+    assert_eq!(
+        utils::project_name_from_root("C:\\Users\\dev\\proxy"),
+        "proxy"
+    );
+    assert_eq!(utils::project_name_from_root(""), "unknown");
+}
+
+#[test]
+fn project_name_from_root_is_trimmed_collapsed_and_capped() {
+    assert_eq!(
+        utils::project_name_from_root("/home/dev/billing api  "),
+        "billing api"
+    );
+    assert_eq!(
+        utils::project_name_from_root("/home/dev/ my \t  side   project "),
+        "my side project"
+    );
+    assert_eq!(utils::project_name_from_root("/tmp/   "), "unknown");
+    assert_eq!(utils::project_name_from_root("/home/dev/🚀-app"), "🚀-app");
+
+    let long = format!("/tmp/{}", "tmp-build-".repeat(30));
+    let name = utils::project_name_from_root(&long);
+    assert_eq!(name.chars().count(), 128);
+    assert!(name.starts_with("tmp-build-tmp-build-"));
+    let spaced = format!("/tmp/{} tail", "x".repeat(127));
+    assert_eq!(utils::project_name_from_root(&spaced), "x".repeat(127));
+}
+
+#[test]
+fn operation_type_for_event_splits_writes_and_reads() {
+    assert_eq!(utils::operation_type_for_event("fileSaved"), "write");
+    assert_eq!(utils::operation_type_for_event("fileAddedLine"), "write");
+    assert_eq!(utils::operation_type_for_event("editorChanged"), "read");
+}
+
+#[test]
+fn helpers_are_re_exported_from_the_crate_root() {
+    assert_eq!(codetime::language_from_extension("a.py"), "python");
+    assert_eq!(codetime::sanitize_relative_path("./a/b.rs"), "a/b.rs");
+    assert_eq!(codetime::project_name_from_root("/x/y"), "y");
+    assert_eq!(codetime::operation_type_for_event("fileCreated"), "write");
+}
+
+fn repo_with_head(name: &str, head: Option<&str>) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("codetime-git-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join(".git")).unwrap();
+    if let Some(head) = head {
+        fs::write(root.join(".git/HEAD"), head).unwrap();
+    }
+    root
+}
+
+/// A worktree root named `folder` with the given manifest files.
+fn root_with_manifests(case: &str, folder: &str, files: &[(&str, &str)]) -> PathBuf {
+    let parent = std::env::temp_dir().join(format!("codetime-ws-{}-{}", case, std::process::id()));
+    let _ = fs::remove_dir_all(&parent);
+    let root = parent.join(folder);
+    fs::create_dir_all(&root).unwrap();
+    for (name, contents) in files {
+        fs::write(root.join(name), contents).unwrap();
+    }
+    root
+}
+
+#[test]
+fn project_name_from_root_reads_manifests_for_generic_folders() {
+    let cargo = root_with_manifests(
+        "cargo",
+        "workspace",
+        &[
+            (
+                "Cargo.toml",
+                "[workspace]\nmembers = [\"crates/*\"]\n\n[package]\nname = \"billing-api\"\n",
+            ),
+            ("package.json", r#"{"name": "billing-web"}"#),
+        ],
+    );
+    assert_eq!(
+        utils::project_name_from_root(cargo.to_str().unwrap()),
+        "billing-api"
+    );
+
+    let virtual_workspace = root_with_manifests(
+        "virtual",
+        "Projects",
+        &[
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+            (
+                "package.json",
+                r#"{"name": "@acme/monorepo", "private": true}"#,
+            ),
+        ],
+    );
+    assert_eq!(
+        utils::project_name_from_root(virtual_workspace.to_str().unwrap()),
+        "@acme/monorepo"
+    );
+
+    for root in [cargo, virtual_workspace] {
+        fs::remove_dir_all(root.parent().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn project_name_from_root_falls_back_to_folder_name() {
+    let broken = root_with_manifests(
+        "broken",
+        "workspace",
+        &[
+            ("Cargo.toml", "[package\nname ="),
+            ("package.json", "{ nope"),
+        ],
+    );
+    assert_eq!(
+        utils::project_name_from_root(broken.to_str().unwrap()),
+        "workspace"
+    );
+
+    let empty = root_with_manifests("empty", "projects", &[]);
+    assert_eq!(
+        utils::project_name_from_root(empty.to_str().unwrap()),
+        "projects"
+    );
+
+    // A descriptive folder name wins even when a manifest is present.
+    let named = root_with_manifests(
+        "named",
+        "code-time",
+        &[("Cargo.toml", "[package]\nname = \"codetime\"\n")],
+    );
+    assert_eq!(
+        utils::project_name_from_root(named.to_str().unwrap()),
+        "code-time"
+    );
+
+    for root in [broken, empty, named] {
+        fs::remove_dir_all(root.parent().unwrap()).unwrap();
+    }
+}
+
+#[test]
+fn read_git_branch_reads_head_ref() {
+    let root = repo_with_head("branch", Some("ref: refs/heads/feature/monorepo-split\n"));
+    assert_eq!(
+        utils::read_git_branch(root.to_str().unwrap()).as_deref(),
+        Some("feature/monorepo-split")
+    );
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn read_git_branch_is_none_for_detached_head() {
+    let root = repo_with_head(
+        "detached",
+        Some("4b825dc642cb6eb9a060e54bf8d69288fbee4904\n"),
+    );
+    assert_eq!(utils::read_git_branch(root.to_str().unwrap()), None);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn read_git_branch_is_none_without_head_file() {
+    let root = repo_with_head("missing", None);
+    assert_eq!(utils::read_git_branch(root.to_str().unwrap()), None);
+    assert_eq!(utils::read_git_branch("/nonexistent/codetime/repo"), None);
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[cfg(feature = "nfc")]
+#[test]
+fn sanitize_relative_path_collapses_nfd_and_nfc() {
+    let nfc = "docs/r\u{e9}sum\u{e9}.md";
+    let nfd = "docs/re\u{301}sume\u{301}.md";
+    assert_ne!(nfc, nfd);
+    assert_eq!(utils::sanitize_relative_path(nfd), nfc);
+    assert_eq!(utils::sanitize_relative_path(nfc), nfc);
+    assert_eq!(
+        utils::sanitize_relative_path("docs/re%CC%81sume%CC%81.md"),
+        nfc
+    );
+    assert_eq!(utils::sanitize_relative_path("src/main.rs"), "src/main.rs");
+}